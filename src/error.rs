@@ -1,5 +1,8 @@
 #[cfg(feature = "enable_serde")] use serde_derive::{Deserialize, Serialize};
-use std::num::ParseIntError;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::num::ParseIntError;
 
 
 pub type MeasureResult<T> = Result<T, MeasureErr>;
@@ -12,6 +15,7 @@ pub enum MeasureErr {
     Overflow,
     Underflow,
     ParseIntError(IntErrorKind),
+    EmptySet,
 }
 
 #[cfg(not(feature = "enable_serde"))]
@@ -20,6 +24,7 @@ pub enum MeasureErr {
     Overflow,
     Underflow,
     ParseIntError(IntErrorKind),
+    EmptySet,
 }
 
 
@@ -45,6 +50,12 @@ pub enum IntErrorKind {
 }
 
 
+impl fmt::Display for MeasureErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 impl From<ParseIntError> for MeasureErr {
     fn from(err: ParseIntError) -> MeasureErr {
         match format!("{}", err).as_str() {
@@ -1,14 +1,23 @@
-/// A library to measure the wall-clock time of Rust expressions.
+//! A library to measure the wall-clock time of Rust expressions.
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")] extern crate std;
 
 mod error;
+mod repeated;
 
 // TODO: If / When possible, replace this with derived De/Serialize impls.
 #[cfg(feature = "enable_serde")] mod serialize;
 
-pub use error::{MeasureErr, MeasureResult};
-pub use chrono::{Duration, Utc};
-use std::fmt;
-use std::ops;
+pub use error::{IntErrorKind, MeasureErr, MeasureResult};
+pub use repeated::MeasurementSet;
+#[cfg(feature = "enable_serde")] pub use serialize::{human, numeric};
+#[cfg(feature = "clock")] pub use chrono::{Duration, Utc};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops;
+use core::str::FromStr;
 
 
 const NS_PER_US: u64   = 1e3 as u64;
@@ -18,10 +27,14 @@ const NS_PER_MIN: u64  = 60 * NS_PER_SEC;
 const NS_PER_HOUR: u64 = 60 * NS_PER_MIN;
 
 
-/// This macro measures the execution time of an expression,
-/// then returns a `(result, measurement)` tuple where:
+/// This macro measures the execution time of an expression using the
+/// wall clock, then returns a `(result, measurement)` tuple where:
 /// - `result` is the result of executing the expression on its own
 /// - `measurement` has type `Measurement`.
+///
+/// Requires the `clock` feature (enabled by default), since it
+/// samples `Utc::now()`.
+#[cfg(feature = "clock")]
 #[macro_export]
 macro_rules! measure {
     ($e:expr) => {{
@@ -33,13 +46,78 @@ macro_rules! measure {
     }}
 }
 
+/// This macro measures the execution time of an expression using a
+/// monotonic (steady) clock, then returns a `(result, measurement)`
+/// tuple where:
+/// - `result` is the result of executing the expression on its own
+/// - `measurement` has type `Measurement`.
+///
+/// Unlike `measure!`, which samples `Utc::now()` and is therefore
+/// subject to wall-clock skew (e.g. NTP steps or manual clock
+/// adjustments can make the post-sample predate the pre-sample),
+/// this macro samples `std::time::Instant::now()`, which is
+/// guaranteed to be non-decreasing. Prefer this macro unless
+/// calendar time is specifically needed.
+///
+/// Requires the `std` feature (enabled by default), since it samples
+/// `std::time::Instant`; unlike `measure!` it does not require the
+/// heavier `clock`/chrono dependency.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! measure_monotonic {
+    ($e:expr) => {{
+        let pre = ::std::time::Instant::now();
+        let result = { $e };
+        let post = ::std::time::Instant::now();
+        let delta = post.duration_since(pre);
+        (result, $crate::Measurement::from_std(delta))
+    }}
+}
+
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Measurement(chrono::Duration);
+/// The sign of a `Measurement`. `Display` uses this to render negative
+/// measurements (e.g. the result of subtracting a larger `Measurement`
+/// from a smaller one) as a `-` prefix followed by the magnitude,
+/// rather than corrupting the magnitude by reinterpreting it as
+/// unsigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sign { Positive, Negative }
+
+
+/// A span of time, stored as a signed nanosecond count. This is the
+/// crate's core, `no_std`-compatible type: it has no dependency on
+/// `chrono` or `std`, so it can be built from any tick source,
+/// including a caller-supplied one on an embedded target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Measurement(i64);
 
 
 impl Measurement {
-    pub fn zero() -> Self { Self(chrono::Duration::zero()) }
+    pub fn zero() -> Self { Self(0) }
+
+    /// Constructs a `Measurement` from a raw signed nanosecond count,
+    /// as sourced from e.g. a caller-supplied tick source.
+    pub fn from_nanos(nanos: i64) -> Self { Self(nanos) }
+
+    /// Returns the raw signed nanosecond count backing this
+    /// `Measurement`.
+    pub fn as_nanos(&self) -> i64 { self.0 }
+
+    /// Constructs a `Measurement` from a `std::time::Duration`, as
+    /// produced by e.g. `Instant::duration_since`. This is the
+    /// counterpart of `measure_monotonic!`'s steady-clock sampling.
+    #[cfg(feature = "std")]
+    pub fn from_std(d: std::time::Duration) -> Self { Self::from(d) }
+
+    /// Returns the sign of this measurement.
+    pub fn sign(&self) -> Sign {
+        if self.0 < 0 { Sign::Negative } else { Sign::Positive }
+    }
+
+    /// Returns `true` if this measurement represents a negative span,
+    /// e.g. as produced by subtracting a larger `Measurement` from a
+    /// smaller one.
+    pub fn is_negative(&self) -> bool { self.sign() == Sign::Negative }
 }
 
 impl Default for Measurement {
@@ -50,181 +128,333 @@ impl ops::Add for Measurement {
     type Output = MeasureResult<Self>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let duration = self.0.checked_add(&rhs.0).ok_or(MeasureErr::Overflow)?;
-        Ok(Self::from(duration))
+        let nanos = self.0.checked_add(rhs.0).ok_or(MeasureErr::Overflow)?;
+        Ok(Self(nanos))
     }
 }
 
 impl ops::Sub for Measurement {
     type Output = MeasureResult<Self>;
 
+    /// Subtracts `rhs` from `self`. If `rhs` is larger than `self`
+    /// the result is a negative `Measurement` (see `Sign`), not an
+    /// error; `MeasureErr::Underflow` is reserved for the case where
+    /// the difference cannot be represented as an i64 nanosecond
+    /// count at all.
     fn sub(self, rhs: Self) -> Self::Output {
-        let duration = self.0.checked_sub(&rhs.0).ok_or(MeasureErr::Underflow)?;
-        Ok(Self::from(duration))
+        let nanos = self.0.checked_sub(rhs.0).ok_or(MeasureErr::Underflow)?;
+        Ok(Self(nanos))
     }
 }
 
 impl fmt::Display for Measurement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.0.num_nanoseconds().map(|nanos| nanos as u64) {
-            None => write!(f, "overflow"),
-            Some(nanos) if nanos < NS_PER_US => write!(f, "{} ns", nanos),
-            Some(nanos) if nanos < NS_PER_MS => {
-                let micros: u64 = nanos / NS_PER_US;
-                let nanos: u64 = nanos % NS_PER_US;
-                if nanos > 0 {
-                    write!(f, "{} µs {} ns", micros, nanos)
-                } else {
-                    write!(f, "{} µs", micros)
-                }
-            },
-            Some(nanos) if nanos < NS_PER_SEC => {
-                let millis: u64 = nanos / NS_PER_MS;
-                let micros: u64 = (nanos % NS_PER_MS) / NS_PER_US;
-                if micros > 0 {
-                    write!(f, "{} ms {} µs", millis, micros)
-                } else {
-                    write!(f, "{} ms", millis)
-                }
-            },
-            Some(nanos) if nanos < NS_PER_MIN => {
-                let secs: u64 = nanos / NS_PER_SEC;
-                let millis: u64 = (nanos % NS_PER_SEC) / NS_PER_MS;
-                if millis > 0 {
-                    write!(f, "{} s {} ms", secs, millis)
-                } else {
-                    write!(f, "{} s", secs)
-                }
-            },
-            Some(nanos) if nanos < NS_PER_HOUR => {
-                let mins: u64 = nanos / NS_PER_MIN;
-                let secs: u64 = (nanos % NS_PER_MIN) / NS_PER_SEC;
-                if secs > 0 {
-                    write!(f, "{} m {} s", mins, secs)
-                } else {
-                    write!(f, "{} m", mins)
-                }
-            },
-            Some(nanos) => {
-                let hours: u64 = nanos / NS_PER_HOUR;
-                let mins: u64 = (nanos % NS_PER_HOUR) / NS_PER_MIN;
-                if mins > 0 {
-                    write!(f, "{} h {} m", hours, mins)
-                } else {
-                    write!(f, "{} h", hours)
-                }
-            },
+        if self.sign() == Sign::Negative { write!(f, "-")?; }
+        write_magnitude(f, self.0.unsigned_abs())
+    }
+}
+
+fn write_magnitude(f: &mut fmt::Formatter, nanos: u64) -> fmt::Result {
+    match nanos {
+        nanos if nanos < NS_PER_US => write!(f, "{} ns", nanos),
+        nanos if nanos < NS_PER_MS => {
+            let micros: u64 = nanos / NS_PER_US;
+            let nanos: u64 = nanos % NS_PER_US;
+            if nanos > 0 {
+                write!(f, "{} µs {} ns", micros, nanos)
+            } else {
+                write!(f, "{} µs", micros)
+            }
+        },
+        nanos if nanos < NS_PER_SEC => {
+            let millis: u64 = nanos / NS_PER_MS;
+            let micros: u64 = (nanos % NS_PER_MS) / NS_PER_US;
+            if micros > 0 {
+                write!(f, "{} ms {} µs", millis, micros)
+            } else {
+                write!(f, "{} ms", millis)
+            }
+        },
+        nanos if nanos < NS_PER_MIN => {
+            let secs: u64 = nanos / NS_PER_SEC;
+            let millis: u64 = (nanos % NS_PER_SEC) / NS_PER_MS;
+            if millis > 0 {
+                write!(f, "{} s {} ms", secs, millis)
+            } else {
+                write!(f, "{} s", secs)
+            }
+        },
+        nanos if nanos < NS_PER_HOUR => {
+            let mins: u64 = nanos / NS_PER_MIN;
+            let secs: u64 = (nanos % NS_PER_MIN) / NS_PER_SEC;
+            if secs > 0 {
+                write!(f, "{} m {} s", mins, secs)
+            } else {
+                write!(f, "{} m", mins)
+            }
+        },
+        nanos => {
+            let hours: u64 = nanos / NS_PER_HOUR;
+            let mins: u64 = (nanos % NS_PER_HOUR) / NS_PER_MIN;
+            if mins > 0 {
+                write!(f, "{} h {} m", hours, mins)
+            } else {
+                write!(f, "{} h", hours)
+            }
+        },
+    }
+}
+
+
+/// Parses the output of `Measurement`'s `Display` impl back into a
+/// `Measurement`, i.e. a whitespace-separated sequence of
+/// `<integer> <unit>` pairs where `unit` is one of `ns`, `µs`/`us`,
+/// `ms`, `s`, `m` or `h`, optionally prefixed with `-` for a negative
+/// `Measurement`. The literal string `"overflow"` is rejected with
+/// `MeasureErr::Overflow`.
+impl FromStr for Measurement {
+    type Err = MeasureErr;
+
+    fn from_str(s: &str) -> MeasureResult<Self> {
+        if s == "overflow" {
+            return Err(MeasureErr::Overflow);
         }
+
+        let (sign, rest) = match s.strip_prefix('-') {
+            Some(rest) => (Sign::Negative, rest),
+            None => (Sign::Positive, s),
+        };
+
+        let tokens: Vec<&str> = rest.split_whitespace().collect();
+        if tokens.is_empty() {
+            return Err(MeasureErr::ParseIntError(IntErrorKind::Empty));
+        }
+        if tokens.len() % 2 != 0 {
+            return Err(MeasureErr::ParseIntError(IntErrorKind::InvalidDigit));
+        }
+
+        let mut total: i64 = 0;
+        for pair in tokens.chunks(2) {
+            let (amount, unit) = (pair[0], pair[1]);
+            let amount: i64 = amount.parse()?;
+            let ns_per_unit: i64 = match unit {
+                "ns" => 1,
+                "µs" | "us" => NS_PER_US as i64,
+                "ms" => NS_PER_MS as i64,
+                "s" => NS_PER_SEC as i64,
+                "m" => NS_PER_MIN as i64,
+                "h" => NS_PER_HOUR as i64,
+                _ => return Err(MeasureErr::ParseIntError(IntErrorKind::InvalidDigit)),
+            };
+            let nanos = amount.checked_mul(ns_per_unit).ok_or(MeasureErr::Overflow)?;
+            total = total.checked_add(nanos).ok_or(MeasureErr::Overflow)?;
+        }
+
+        if sign == Sign::Negative {
+            total = total.checked_neg().ok_or(MeasureErr::Overflow)?;
+        }
+
+        Ok(Self(total))
     }
 }
 
 
+#[cfg(feature = "clock")]
 impl From<Measurement> for chrono::Duration {
-    fn from(m: Measurement) -> chrono::Duration { m.0 }
+    fn from(m: Measurement) -> chrono::Duration { chrono::Duration::nanoseconds(m.0) }
 }
 
+#[cfg(feature = "clock")]
 impl From<chrono::Duration> for Measurement {
-    fn from(d: chrono::Duration) -> Self { Self(d) }
+    fn from(d: chrono::Duration) -> Self {
+        Self(d.num_nanoseconds().unwrap_or(i64::max_value()))
+    }
 }
 
+#[cfg(feature = "std")]
+impl From<std::time::Duration> for Measurement {
+    fn from(d: std::time::Duration) -> Self {
+        use core::convert::TryFrom;
+        Self(i64::try_from(d.as_nanos()).unwrap_or(i64::max_value()))
+    }
+}
 
 
 
 
 #[cfg(test)]
 mod tests {
-    use crate::Measurement;
-    use chrono::Duration;
+    use crate::{IntErrorKind, MeasureErr, Measurement};
+    use alloc::format;
+
+    fn hours(n: i64) -> i64 { n * crate::NS_PER_HOUR as i64 }
+    fn mins(n: i64) -> i64 { n * crate::NS_PER_MIN as i64 }
+    fn secs(n: i64) -> i64 { n * crate::NS_PER_SEC as i64 }
+    fn millis(n: i64) -> i64 { n * crate::NS_PER_MS as i64 }
+    fn micros(n: i64) -> i64 { n * crate::NS_PER_US as i64 }
 
     #[test]
+    #[cfg(all(feature = "clock", feature = "std"))]
     fn readme_md_example() {
         use std::fs::File;
         use std::io::Read;
+        use std::string::String;
+        use std::vec::Vec;
 
         let (contents, measurement) = measure! {{
             let mut file = File::open("Cargo.lock")
                 .expect("failed to open Cargo.lock");
-            let mut contents = vec![];
+            let mut contents = Vec::new();
             file.read_to_end(&mut contents)
                 .expect("failed to read Cargo.lock");
             String::from_utf8(contents)
                 .expect("failed to extract contents to String")
         }};
 
-        println!("contents: {:?}", contents);
-        println!("opening and reading Cargo.lock took {}", measurement);
+        std::println!("contents: {:?}", contents);
+        std::println!("opening and reading Cargo.lock took {}", measurement);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn measure_monotonic_basic() {
+        use std::string::String;
+
+        let (contents, measurement) = measure_monotonic! {{
+            let mut acc = String::new();
+            for _ in 0..100 { acc.push('x'); }
+            acc
+        }};
+
+        assert_eq!(contents.len(), 100);
+        std::println!("measure_monotonic took {}", measurement);
     }
 
     #[test]
     fn format_hours_one_chunk() {
-        let one_chunk = Measurement(Duration::hours(10));
+        let one_chunk = Measurement(hours(10));
         assert_eq!("10 h", format!("{}", one_chunk));
     }
 
     #[test]
     fn format_hours_two_chunks() {
-        let (hours, mins) = (Duration::hours(3), Duration::minutes(3));
-        let two_chunks = Measurement(hours.checked_add(&mins).unwrap());
+        let two_chunks = Measurement(hours(3) + mins(3));
         assert_eq!("3 h 3 m", format!("{}", two_chunks));
     }
 
     #[test]
     fn format_minutes_one_chunk() {
-        let one_chunk = Measurement(Duration::minutes(10));
+        let one_chunk = Measurement(mins(10));
         assert_eq!("10 m", format!("{}", one_chunk));
     }
 
     #[test]
     fn format_minutes_two_chunks() {
-        let (mins, secs) = (Duration::minutes(3), Duration::seconds(3));
-        let two_chunks = Measurement(mins.checked_add(&secs).unwrap());
+        let two_chunks = Measurement(mins(3) + secs(3));
         assert_eq!("3 m 3 s", format!("{}", two_chunks));
     }
 
     #[test]
     fn format_seconds_one_chunk() {
-        let one_chunk = Measurement(Duration::seconds(10));
+        let one_chunk = Measurement(secs(10));
         assert_eq!("10 s", format!("{}", one_chunk));
     }
 
     #[test]
     fn format_seconds_two_chunks() {
-        let (secs, millis) = (Duration::seconds(3), Duration::milliseconds(3));
-        let two_chunks = Measurement(secs.checked_add(&millis).unwrap());
+        let two_chunks = Measurement(secs(3) + millis(3));
         assert_eq!("3 s 3 ms", format!("{}", two_chunks));
     }
 
     #[test]
     fn format_milliseconds_one_chunk() {
-        let one_chunk = Measurement(Duration::milliseconds(10));
+        let one_chunk = Measurement(millis(10));
         assert_eq!("10 ms", format!("{}", one_chunk));
     }
 
     #[test]
     fn format_milliseconds_two_chunks() {
-        let millis = Duration::milliseconds(3);
-        let micros = Duration::microseconds(3);
-        let two_chunks = Measurement(millis.checked_add(&micros).unwrap());
+        let two_chunks = Measurement(millis(3) + micros(3));
         assert_eq!("3 ms 3 µs", format!("{}", two_chunks));
     }
 
     #[test]
     fn format_microseconds_one_chunk() {
-        let one_chunk = Measurement(Duration::microseconds(10));
+        let one_chunk = Measurement(micros(10));
         assert_eq!("10 µs", format!("{}", one_chunk));
     }
 
     #[test]
     fn format_microseconds_two_chunks() {
-        let micros = Duration::microseconds(3);
-        let nanos = Duration::nanoseconds(3);
-        let two_chunks = Measurement(micros.checked_add(&nanos).unwrap());
+        let two_chunks = Measurement(micros(3) + 3);
         assert_eq!("3 µs 3 ns", format!("{}", two_chunks));
     }
 
     #[test]
     fn format_nanoseconds_one_chunk() {
-        let one_chunk = Measurement(Duration::nanoseconds(10));
+        let one_chunk = Measurement(10);
         assert_eq!("10 ns", format!("{}", one_chunk));
     }
+
+    #[test]
+    fn parse_roundtrip_two_chunks() {
+        let measurement = Measurement(hours(3) + mins(3));
+        let parsed: Measurement = format!("{}", measurement).parse()
+            .expect("failed to parse");
+        assert_eq!(measurement, parsed);
+    }
+
+    #[test]
+    fn parse_single_unit() {
+        let parsed: Measurement = "250 ms".parse().expect("failed to parse");
+        assert_eq!(Measurement(millis(250)), parsed);
+    }
+
+    #[test]
+    fn parse_accepts_us_alias() {
+        let parsed: Measurement = "3 us".parse().expect("failed to parse");
+        assert_eq!(Measurement(micros(3)), parsed);
+    }
+
+    #[test]
+    fn parse_rejects_overflow_literal() {
+        let result = "overflow".parse::<Measurement>();
+        assert_eq!(Err(MeasureErr::Overflow), result);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_unit() {
+        let result = "3 fortnights".parse::<Measurement>();
+        assert_eq!(Err(MeasureErr::ParseIntError(IntErrorKind::InvalidDigit)), result);
+    }
+
+    #[test]
+    fn parse_rejects_empty_string() {
+        let result = "".parse::<Measurement>();
+        assert_eq!(Err(MeasureErr::ParseIntError(IntErrorKind::Empty)), result);
+    }
+
+    #[test]
+    fn parse_rejects_odd_token_count() {
+        let result = "3 h 3".parse::<Measurement>();
+        assert_eq!(Err(MeasureErr::ParseIntError(IntErrorKind::InvalidDigit)), result);
+    }
+
+    #[test]
+    fn sub_underflow_yields_negative_measurement() {
+        let small = Measurement(secs(1));
+        let big = Measurement(secs(3));
+        let diff = (small - big).expect("true i64 overflow should not occur here");
+        assert!(diff.is_negative());
+        assert_eq!("-2 s", format!("{}", diff));
+    }
+
+    #[test]
+    fn negative_measurement_roundtrips_through_display_and_parse() {
+        let diff = Measurement(-(hours(3) + mins(3)));
+        let rendered = format!("{}", diff);
+        assert_eq!("-3 h 3 m", rendered);
+        let parsed: Measurement = rendered.parse().expect("failed to parse");
+        assert_eq!(diff, parsed);
+    }
 }
@@ -0,0 +1,185 @@
+/// This module provides repeated-run benchmarking support: running
+/// an expression multiple times and summarizing the resulting
+/// `Measurement`s with descriptive statistics.
+
+use crate::{MeasureErr, MeasureResult, Measurement};
+use alloc::vec::Vec;
+use core::fmt;
+
+
+/// This macro runs an expression `n` times using the monotonic clock
+/// (see `measure_monotonic!`), discarding each iteration's result,
+/// and collects the per-iteration timings into a `MeasurementSet`.
+/// Use the resulting set's statistics (`min`, `max`, `mean`,
+/// `median`, `std_dev`) to characterize noisy workloads.
+///
+/// Requires the `std` feature (enabled by default), since it builds
+/// on `measure_monotonic!`.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! measure_repeatedly {
+    ($n:expr, $e:expr) => {{
+        let n = $n;
+        let mut measurements = $crate::MeasurementSet::with_capacity(n);
+        for _ in 0..n {
+            let (_result, measurement) = $crate::measure_monotonic!($e);
+            measurements.push(measurement);
+        }
+        measurements
+    }}
+}
+
+
+/// A set of `Measurement`s gathered from repeated executions of the
+/// same expression (see `measure_repeatedly!`), together with the
+/// descriptive statistics needed to characterize noisy workloads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeasurementSet {
+    measurements: Vec<Measurement>,
+}
+
+impl MeasurementSet {
+    pub fn new(measurements: Vec<Measurement>) -> Self { Self { measurements } }
+
+    /// Creates an empty set with room for `capacity` measurements
+    /// without reallocating, mirroring `Vec::with_capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { measurements: Vec::with_capacity(capacity) }
+    }
+
+    /// Appends a `Measurement` to the set.
+    pub fn push(&mut self, measurement: Measurement) {
+        self.measurements.push(measurement);
+    }
+
+    pub fn len(&self) -> usize { self.measurements.len() }
+
+    pub fn is_empty(&self) -> bool { self.measurements.is_empty() }
+
+    pub fn measurements(&self) -> &[Measurement] { &self.measurements }
+
+    fn nanos(&self) -> Vec<i64> {
+        self.measurements.iter().map(|m| m.0).collect()
+    }
+
+    /// Returns the smallest `Measurement` in the set.
+    pub fn min(&self) -> MeasureResult<Measurement> {
+        self.measurements.iter().cloned().min().ok_or(MeasureErr::EmptySet)
+    }
+
+    /// Returns the largest `Measurement` in the set.
+    pub fn max(&self) -> MeasureResult<Measurement> {
+        self.measurements.iter().cloned().max().ok_or(MeasureErr::EmptySet)
+    }
+
+    /// Returns the arithmetic mean of the set.
+    pub fn mean(&self) -> MeasureResult<Measurement> {
+        if self.is_empty() { return Err(MeasureErr::EmptySet); }
+        let nanos = self.nanos();
+        let sum: i64 = nanos.iter().try_fold(0i64, |acc, &n| acc.checked_add(n))
+            .ok_or(MeasureErr::Overflow)?;
+        Ok(Measurement::from_nanos(sum / nanos.len() as i64))
+    }
+
+    /// Returns the median of the set, averaging the two middle
+    /// values when the set has an even number of elements.
+    pub fn median(&self) -> MeasureResult<Measurement> {
+        if self.is_empty() { return Err(MeasureErr::EmptySet); }
+        let mut sorted = self.measurements.clone();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 1 {
+            return Ok(sorted[mid]);
+        }
+        let sum = (sorted[mid - 1] + sorted[mid])?;
+        Ok(Measurement::from_nanos(sum.0 / 2))
+    }
+
+    /// Returns the (population) standard deviation of the set.
+    ///
+    /// Requires the `std` feature (enabled by default), since `core`
+    /// has no square root function.
+    #[cfg(feature = "std")]
+    pub fn std_dev(&self) -> MeasureResult<Measurement> {
+        if self.is_empty() { return Err(MeasureErr::EmptySet); }
+        let nanos = self.nanos();
+        let n = nanos.len() as f64;
+        let mean = nanos.iter().sum::<i64>() as f64 / n;
+        let variance = nanos.iter()
+            .map(|&x| { let delta = x as f64 - mean; delta * delta })
+            .sum::<f64>() / n;
+        Ok(Measurement::from_nanos(variance.sqrt() as i64))
+    }
+}
+
+impl fmt::Display for MeasurementSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.mean(), self.min(), self.max()) {
+            (Ok(mean), Ok(min), Ok(max)) =>
+                write!(f, "mean {} (min {}, max {}, n={})", mean, min, max, self.len()),
+            _ => write!(f, "overflow"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{Measurement, MeasurementSet};
+    use alloc::format;
+    use alloc::vec;
+
+    fn set(millis: &[i64]) -> MeasurementSet {
+        MeasurementSet::new(
+            millis.iter().map(|&ms| Measurement::from_nanos(ms * 1_000_000)).collect()
+        )
+    }
+
+    #[test]
+    fn min_and_max() {
+        let set = set(&[5, 2, 8, 3]);
+        assert_eq!(Measurement::from_nanos(2_000_000), set.min().unwrap());
+        assert_eq!(Measurement::from_nanos(8_000_000), set.max().unwrap());
+    }
+
+    #[test]
+    fn mean() {
+        let set = set(&[2, 4, 6]);
+        assert_eq!(Measurement::from_nanos(4_000_000), set.mean().unwrap());
+    }
+
+    #[test]
+    fn median_odd_count() {
+        let set = set(&[5, 1, 3]);
+        assert_eq!(Measurement::from_nanos(3_000_000), set.median().unwrap());
+    }
+
+    #[test]
+    fn median_even_count() {
+        let set = set(&[1, 2, 3, 4]);
+        assert_eq!(Measurement::from_nanos(2_500_000), set.median().unwrap());
+    }
+
+    #[test]
+    fn empty_set_errors() {
+        let set = MeasurementSet::new(vec![]);
+        assert!(set.min().is_err());
+        assert!(set.mean().is_err());
+        assert!(set.median().is_err());
+        #[cfg(feature = "std")]
+        assert!(set.std_dev().is_err());
+    }
+
+    #[test]
+    fn display_format() {
+        let set = set(&[2, 3, 4]);
+        assert_eq!("mean 3 ms (min 2 ms, max 4 ms, n=3)", format!("{}", set));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn measure_repeatedly_collects_n_samples() {
+        let set = measure_repeatedly!(5, { 1 + 1 });
+        assert_eq!(5, set.len());
+    }
+}
@@ -1,20 +1,32 @@
 /// This module provides de/serialization for the Measurement type.
+///
+/// By default `Measurement` serializes the same way it always has:
+/// a string holding its signed nanosecond count (e.g.
+/// `"10980000000000"`). Deserialization is deliberately *flexible*
+/// (in the spirit of serde_with's `Flexible`): a `Measurement` can be
+/// read back from that same nanosecond string, from a bare
+/// signed/unsigned integer, or from a human-formatted `Display`
+/// string (e.g. `"3 h 3 m"`), so values written by any of the modes
+/// below round-trip regardless of which one wrote them.
+///
+/// To pin a field to the human-readable or the bare-integer wire
+/// format, use the `human` or `numeric` helper modules with
+/// `#[serde(with = "...")]`, mirroring how `chrono::serde::ts_seconds`
+/// et al. work.
 
-// NOTE: If `Measurement`'s chrono::Duration field should ever support
-//        proper de/serialization, this entire module can be removed.
+// NOTE: These impls are hand-written rather than derived because a
+//        derived impl would write the i64 field as a bare integer and
+//        would not accept the string forms described above.
 
 use crate::Measurement;
-use chrono;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
-use std::fmt;
 
 impl Serialize for Measurement {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-        if let Some(nanos) = self.0.num_nanoseconds() {
-            s.serialize_str(&format!("{}", nanos))
-        } else {
-            s.serialize_str("overflow")
-        }
+        s.serialize_str(&format!("{}", self.0))
     }
 }
 
@@ -24,19 +36,34 @@ impl<'de> serde::de::Visitor<'de> for MeasurementVisitor {
     type Value = Measurement;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("a Measurement that is within range")
+        formatter.write_str(
+            "a Measurement: a nanosecond integer, a nanosecond string, \
+             or a human-formatted duration string"
+        )
+    }
+
+    fn visit_i64<E>(self, nanos: i64) -> Result<Measurement, E>
+    where E: serde::de::Error {
+        Ok(Measurement(nanos))
+    }
+
+    fn visit_u64<E>(self, nanos: u64) -> Result<Measurement, E>
+    where E: serde::de::Error {
+        self.visit_i64(nanos as i64)
     }
 
     fn visit_str<E>(self, string: &str) -> Result<Measurement, E>
     where E: serde::de::Error {
-        let serde_err = |msg| Err(serde::de::Error::custom(msg));
         match string {
-            "overflow" => serde_err("Failed to serialize Duration: overflow"),
-            _ => match string.parse() {
-                Ok(n) => Ok(Measurement(chrono::Duration::nanoseconds(n))),
-                Err(_from_str_err) => serde_err(
-                    &format!("Failed to parse Duration: {}", string)
-                ),
+            "overflow" => Err(serde::de::Error::custom(
+                "Failed to deserialize Measurement: overflow"
+            )),
+            _ => match string.parse::<i64>() {
+                Ok(nanos) => Ok(Measurement(nanos)),
+                Err(_parse_int_err) => string.parse::<Measurement>().map_err(|_from_str_err| {
+                    let msg = format!("Failed to parse Measurement: {}", string);
+                    serde::de::Error::custom(msg)
+                }),
             }
         }
     }
@@ -49,7 +76,43 @@ impl<'de> serde::de::Visitor<'de> for MeasurementVisitor {
 
 impl<'de> Deserialize<'de> for Measurement {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        d.deserialize_str(MeasurementVisitor)
+        d.deserialize_any(MeasurementVisitor)
+    }
+}
+
+
+/// Serializes/deserializes a `Measurement` as its human-readable
+/// `Display` form (e.g. `"3 h 3 m"`). Use via
+/// `#[serde(with = "tempus_fugit::human")]` on a struct field.
+pub mod human {
+    use crate::Measurement;
+    use alloc::string::String;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(m: &Measurement, s: S) -> Result<S::Ok, S::Error> {
+        alloc::format!("{}", m).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Measurement, D::Error> {
+        String::deserialize(d)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+
+/// Serializes/deserializes a `Measurement` as a bare signed
+/// nanosecond integer, rather than the default nanosecond *string*.
+/// Use via `#[serde(with = "tempus_fugit::numeric")]` on a struct
+/// field.
+pub mod numeric {
+    use crate::Measurement;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(m: &Measurement, s: S) -> Result<S::Ok, S::Error> {
+        m.0.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Measurement, D::Error> {
+        Ok(Measurement(i64::deserialize(d)?))
     }
 }
 
@@ -57,28 +120,100 @@ impl<'de> Deserialize<'de> for Measurement {
 #[cfg(test)]
 mod tests {
     use crate::Measurement;
-    use chrono::Duration;
+    use serde_derive::{Deserialize, Serialize};
     use serde_json;
 
+    fn hours(n: i64) -> i64 { n * 60 * 60 * 1_000_000_000 }
+    fn mins(n: i64) -> i64 { n * 60 * 1_000_000_000 }
+    fn secs(n: i64) -> i64 { n * 1_000_000_000 }
+    fn millis(n: i64) -> i64 { n * 1_000_000 }
+
     #[test]
     fn serialize() {
-        let (hours, mins) = (Duration::hours(3), Duration::minutes(3));
-        let measurement = Measurement(hours.checked_add(&mins).unwrap());
+        let measurement = Measurement(hours(3) + mins(3));
         let json_string = serde_json::to_string(&measurement)
             .expect("failed to serialize");
         assert_eq!(json_string, "\"10980000000000\"");
     }
 
+    #[test]
+    fn serialize_negative() {
+        let measurement = Measurement(-secs(2));
+        let json_string = serde_json::to_string(&measurement)
+            .expect("failed to serialize");
+        assert_eq!(json_string, "\"-2000000000\"");
+    }
+
+    #[test]
+    fn deserialize_negative() {
+        const JSON_STRING: &str = "\"-2000000000\"";
+        let deserialized: Measurement = serde_json::from_str(&JSON_STRING)
+            .expect("failed to deserialize");
+        assert_eq!(Measurement(-secs(2)), deserialized);
+    }
+
     #[test]
     fn deserialize() {
         const JSON_STRING: &str = "\"10980000000000\"";
-        println!("JSON: {}", JSON_STRING);
         let deserialized = serde_json::from_str(&JSON_STRING)
             .expect("failed to deserialize");
-        let (hours, mins) = (Duration::hours(3), Duration::minutes(3));
-        let measurement = Measurement(hours.checked_add(&mins).unwrap());
+        let measurement = Measurement(hours(3) + mins(3));
         assert_eq!(measurement, deserialized,
                    "measurement ({}) != deserialized ({})",
                    measurement, deserialized);
     }
+
+    #[test]
+    fn deserialize_from_bare_integer() {
+        const JSON: &str = "10980000000000";
+        let deserialized: Measurement = serde_json::from_str(JSON)
+            .expect("failed to deserialize");
+        assert_eq!(Measurement(hours(3) + mins(3)), deserialized);
+    }
+
+    #[test]
+    fn deserialize_from_human_string() {
+        const JSON: &str = "\"3 h 3 m\"";
+        let deserialized: Measurement = serde_json::from_str(JSON)
+            .expect("failed to deserialize");
+        assert_eq!(Measurement(hours(3) + mins(3)), deserialized);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct HumanWrapper {
+        #[serde(with = "crate::human")]
+        duration: Measurement,
+    }
+
+    #[test]
+    fn human_module_round_trip() {
+        let measurement = Measurement(hours(3) + mins(3));
+        let wrapper = HumanWrapper { duration: measurement };
+
+        let json = serde_json::to_string(&wrapper).expect("failed to serialize");
+        assert_eq!(json, "{\"duration\":\"3 h 3 m\"}");
+
+        let roundtripped: HumanWrapper = serde_json::from_str(&json)
+            .expect("failed to deserialize");
+        assert_eq!(measurement, roundtripped.duration);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct NumericWrapper {
+        #[serde(with = "crate::numeric")]
+        duration: Measurement,
+    }
+
+    #[test]
+    fn numeric_module_round_trip() {
+        let measurement = Measurement(millis(3));
+        let wrapper = NumericWrapper { duration: measurement };
+
+        let json = serde_json::to_string(&wrapper).expect("failed to serialize");
+        assert_eq!(json, "{\"duration\":3000000}");
+
+        let roundtripped: NumericWrapper = serde_json::from_str(&json)
+            .expect("failed to deserialize");
+        assert_eq!(measurement, roundtripped.duration);
+    }
 }